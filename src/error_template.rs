@@ -0,0 +1,72 @@
+use crate::todo::TodoAppError;
+use leptos::prelude::*;
+#[cfg(feature = "ssr")]
+use leptos_axum::ResponseOptions;
+
+/// Renders each error collected by an `<ErrorBoundary>`, using
+/// [`TodoAppError::status_code`] to set the response status on the server
+/// and to pick a message tailored to what actually went wrong.
+#[component]
+pub fn ErrorTemplate(
+    #[prop(optional)] outside_errors: Option<Errors>,
+    #[prop(optional)] errors: Option<RwSignal<Errors>>,
+) -> impl IntoView {
+    let errors = match outside_errors {
+        Some(e) => RwSignal::new(e),
+        None => errors.expect("No Errors found and we expected errors!"),
+    };
+
+    // server functions hand their errors over wrapped in `ServerFnError`, so
+    // look through that wrapper as well as at a bare `TodoAppError`
+    fn as_todo_app_error(error: &leptos::error::Error) -> Option<&TodoAppError> {
+        use leptos::server_fn::ServerFnError;
+        error.downcast_ref::<TodoAppError>().or_else(|| {
+            match error.downcast_ref::<ServerFnError<TodoAppError>>() {
+                Some(ServerFnError::WrappedServerError(e)) => Some(e),
+                _ => None,
+            }
+        })
+    }
+
+    #[cfg(feature = "ssr")]
+    {
+        // the first typed error decides the response status; an untyped
+        // error (anything that isn't a `TodoAppError`) falls back to 500
+        let status = errors
+            .get_untracked()
+            .into_iter()
+            .find_map(|(_, error)| as_todo_app_error(&error).map(TodoAppError::status_code))
+            .unwrap_or(http::StatusCode::INTERNAL_SERVER_ERROR);
+        if let Some(response) = use_context::<ResponseOptions>() {
+            response.set_status(status);
+        }
+    }
+
+    view! {
+        <h1>{move || if errors.get().len() > 1 { "Errors" } else { "Error" }}</h1>
+        <For
+            each=move || errors.get().into_iter().enumerate()
+            key=|(index, _)| *index
+            let:error
+        >
+            {
+                let message = match as_todo_app_error(&error.1.1) {
+                    Some(TodoAppError::NotFound) => {
+                        "That todo doesn't exist anymore.".to_string()
+                    }
+                    Some(TodoAppError::DbUnavailable) => {
+                        "The database is unavailable right now.".to_string()
+                    }
+                    Some(TodoAppError::InvalidTitle) => {
+                        "Titles can't be empty.".to_string()
+                    }
+                    Some(TodoAppError::MissingField) => {
+                        "The request was missing a required field.".to_string()
+                    }
+                    None => error.1.1.to_string(),
+                };
+                view! { <p>"Error: " {message}</p> }
+            }
+        </For>
+    }
+}